@@ -0,0 +1,105 @@
+use core::fmt;
+
+use crate::level::{scene, Level, World};
+
+/// Why the splitter started, split or reset the timer
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// New game scene with a loading rising edge
+    Start { scene: u32 },
+    /// Back in the hub after the level's results screen or ending cutscene
+    LevelExit { level: Level, old: u32 },
+    /// Coming from the hub into the level
+    LevelEntry { level: Level },
+    /// Toc-Man's final QTE succeeded
+    BossQte { level: Level },
+    /// World splits: the world's closing level was exited or its final QTE succeeded
+    WorldComplete { world: World, level: Level },
+    /// Skipping splits for disabled levels: a disabled level reached the point where it would have split
+    LevelSkip { level: Level },
+    /// Route splits: a split was held back because the current segment expects another level
+    OffRoute { level: Level, expected: Option<Level> },
+    Reset { scene: u32 },
+    /// IL mode: the level became playable
+    LevelStart { level: Level },
+    /// IL mode: the level's results screen came up
+    LevelComplete { level: Level },
+    /// IL mode: the level was reloaded
+    LevelRestart { level: Level },
+    /// IL mode: the level was left without completing it
+    LevelQuit { level: Level, scene: u32 },
+}
+
+impl Event {
+    /// Level a split or skip is for
+    pub const fn level(self) -> Option<Level> {
+        match self {
+            Self::LevelExit { level, .. }
+            | Self::LevelEntry { level }
+            | Self::BossQte { level }
+            | Self::WorldComplete { level, .. }
+            | Self::LevelSkip { level, .. }
+            | Self::LevelComplete { level } => Some(level),
+            _ => None,
+        }
+    }
+
+    pub const fn is_split(self) -> bool {
+        matches!(
+            self,
+            Self::LevelExit { .. } | Self::LevelEntry { .. } | Self::BossQte { .. }
+                | Self::WorldComplete { .. }
+                | Self::LevelComplete { .. }
+        )
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Start { scene } => write!(f, "start: scene {scene} + loading rising edge"),
+            Self::LevelExit { level, old } => {
+                let via = if old == scene::RESULTS { "results" } else { "cutscene" };
+                write!(f, "split: level {} exited via {via} (old={old})", level as u32)
+            }
+            Self::LevelEntry { level } => write!(f, "split: level {} entered", level as u32),
+            Self::BossQte { level } => write!(f, "split: level {} final QTE succeeded", level as u32),
+            Self::WorldComplete { world, level } => {
+                write!(f, "split: world {} completed by level {}", world.index(), level as u32)
+            }
+            Self::LevelSkip { level } => write!(f, "skip: level {} is disabled", level as u32),
+            Self::OffRoute { level, expected: Some(expected) } => {
+                write!(f, "off route: level {} completed, expected {}", level as u32, expected as u32)
+            }
+            Self::OffRoute { level, expected: None } => {
+                write!(f, "off route: level {} completed after the end of the route", level as u32)
+            }
+            Self::Reset { scene } => write!(f, "reset: scene changed to {scene}"),
+            Self::LevelStart { level } => write!(f, "start: level {} ready", level as u32),
+            Self::LevelComplete { level } => write!(f, "split: level {} completed", level as u32),
+            Self::LevelRestart { level } => write!(f, "reset: level {} restarted", level as u32),
+            Self::LevelQuit { level, scene } => write!(f, "reset: level {} quit to scene {scene}", level as u32),
+        }
+    }
+}
+
+/// The last few events, oldest ones overwritten first
+#[derive(Default)]
+pub struct EventLog {
+    events: [Option<Event>; 16],
+    next: usize,
+}
+
+impl EventLog {
+    pub fn push(&mut self, event: Event) {
+        self.events[self.next] = Some(event);
+        self.next = (self.next + 1) % self.events.len();
+    }
+
+    /// Recorded events, newest first
+    pub fn iter(&self) -> impl Iterator<Item = Event> + '_ {
+        // Everything before `next` was written after everything from `next` on
+        let (newer, older) = self.events.split_at(self.next);
+        older.iter().chain(newer).rev().flatten().copied()
+    }
+}
@@ -0,0 +1,217 @@
+use asr::settings::Gui;
+
+/// Values of `SceneManager.m_eCurrentScene` that do not correspond to a playable level
+pub mod scene {
+    /// World selection hub
+    pub const HUB: u32 = 1;
+    /// Title screen
+    pub const TITLE_SCREEN: u32 = 2;
+    /// End-of-level results tally
+    pub const RESULTS: u32 = 3;
+    /// Opening scene shown when starting a new file
+    pub const NEW_GAME: u32 = 4;
+
+    /// Boss cutscenes are reported with ids above 1000
+    pub const fn is_cutscene(scene: u32) -> bool {
+        scene > 1000
+    }
+}
+
+#[derive(Gui, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum World {
+    /// Pirate
+    #[default]
+    Pirate,
+    /// Ruins
+    Ruins,
+    /// Space
+    Space,
+    /// Funhouse
+    Funhouse,
+    /// Mill
+    Mill,
+    /// Haunted
+    Haunted,
+}
+
+impl World {
+    pub const ALL: [Self; 6] = [
+        Self::Pirate,
+        Self::Ruins,
+        Self::Space,
+        Self::Funhouse,
+        Self::Mill,
+        Self::Haunted,
+    ];
+
+    /// 1-based world number, as shown in game
+    pub const fn index(self) -> u32 {
+        match self {
+            Self::Pirate => 1,
+            Self::Ruins => 2,
+            Self::Space => 3,
+            Self::Funhouse => 4,
+            Self::Mill => 5,
+            Self::Haunted => 6,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Pirate => "Pirate",
+            Self::Ruins => "Ruins",
+            Self::Space => "Space",
+            Self::Funhouse => "Funhouse",
+            Self::Mill => "Mill",
+            Self::Haunted => "Haunted",
+        }
+    }
+}
+
+/// Playable levels, using their `m_eCurrentScene` id as discriminant
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Level {
+    BuccaneerBeach = 101,
+    CorsairsCove = 102,
+    CrazyCannonade = 103,
+    HmsWindbag = 104,
+    CrisisCavern = 201,
+    ManicMines = 202,
+    AnubisRex = 203,
+    SpaceRace = 301,
+    FarOut = 302,
+    GimmeSpace = 303,
+    KingGalaxian = 304,
+    ClowningAround = 401,
+    BarrelBlast = 402,
+    BarrelDizzy = 403,
+    ClownPrix = 404,
+    PerilousPipes = 501,
+    UnderPressure = 502,
+    DownTheTubes = 503,
+    KromeKeeper = 504,
+    GhostlyGarden = 601,
+    CreepyCatacombs = 602,
+    GraveDanger = 603,
+    TocMansLair = 604,
+}
+
+impl Level {
+    /// Every level, in game order
+    pub const ALL: [Self; 23] = [
+        Self::BuccaneerBeach,
+        Self::CorsairsCove,
+        Self::CrazyCannonade,
+        Self::HmsWindbag,
+        Self::CrisisCavern,
+        Self::ManicMines,
+        Self::AnubisRex,
+        Self::SpaceRace,
+        Self::FarOut,
+        Self::GimmeSpace,
+        Self::KingGalaxian,
+        Self::ClowningAround,
+        Self::BarrelBlast,
+        Self::BarrelDizzy,
+        Self::ClownPrix,
+        Self::PerilousPipes,
+        Self::UnderPressure,
+        Self::DownTheTubes,
+        Self::KromeKeeper,
+        Self::GhostlyGarden,
+        Self::CreepyCatacombs,
+        Self::GraveDanger,
+        Self::TocMansLair,
+    ];
+
+    pub const fn world(self) -> World {
+        match self {
+            Self::BuccaneerBeach | Self::CorsairsCove | Self::CrazyCannonade | Self::HmsWindbag => World::Pirate,
+            Self::CrisisCavern | Self::ManicMines | Self::AnubisRex => World::Ruins,
+            Self::SpaceRace | Self::FarOut | Self::GimmeSpace | Self::KingGalaxian => World::Space,
+            Self::ClowningAround | Self::BarrelBlast | Self::BarrelDizzy | Self::ClownPrix => World::Funhouse,
+            Self::PerilousPipes | Self::UnderPressure | Self::DownTheTubes | Self::KromeKeeper => World::Mill,
+            Self::GhostlyGarden | Self::CreepyCatacombs | Self::GraveDanger | Self::TocMansLair => World::Haunted,
+        }
+    }
+
+    /// 1-based world number, e.g. 2 for Anubis Rex (2.3)
+    pub const fn world_index(self) -> u32 {
+        self.world().index()
+    }
+
+    /// 1-based stage number within the world, e.g. 3 for Anubis Rex (2.3)
+    pub const fn stage_index(self) -> u32 {
+        self as u32 % 100
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::BuccaneerBeach => "Buccaneer Beach",
+            Self::CorsairsCove => "Corsair's Cove",
+            Self::CrazyCannonade => "Crazy Cannonade",
+            Self::HmsWindbag => "HMS Windbag",
+            Self::CrisisCavern => "Crisis Cavern",
+            Self::ManicMines => "Manic Mines",
+            Self::AnubisRex => "Anubis Rex",
+            Self::SpaceRace => "Space Race",
+            Self::FarOut => "Far Out",
+            Self::GimmeSpace => "Gimme Space",
+            Self::KingGalaxian => "King Galaxian",
+            Self::ClowningAround => "Clowning Around",
+            Self::BarrelBlast => "Barrel Blast",
+            Self::BarrelDizzy => "Barrel Dizzy",
+            Self::ClownPrix => "Clown Prix",
+            Self::PerilousPipes => "Perilous Pipes",
+            Self::UnderPressure => "Under Pressure",
+            Self::DownTheTubes => "Down the Tubes",
+            Self::KromeKeeper => "Krome Keeper",
+            Self::GhostlyGarden => "Ghostly Garden",
+            Self::CreepyCatacombs => "Creepy Catacombs",
+            Self::GraveDanger => "Grave Danger",
+            Self::TocMansLair => "Toc-Man's Lair",
+        }
+    }
+
+    /// Whether this is the boss stage closing its world
+    pub const fn is_boss(self) -> bool {
+        matches!(
+            self,
+            Self::HmsWindbag | Self::AnubisRex | Self::KingGalaxian | Self::ClownPrix | Self::KromeKeeper | Self::TocMansLair
+        )
+    }
+}
+
+impl TryFrom<u32> for Level {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            101 => Self::BuccaneerBeach,
+            102 => Self::CorsairsCove,
+            103 => Self::CrazyCannonade,
+            104 => Self::HmsWindbag,
+            201 => Self::CrisisCavern,
+            202 => Self::ManicMines,
+            203 => Self::AnubisRex,
+            301 => Self::SpaceRace,
+            302 => Self::FarOut,
+            303 => Self::GimmeSpace,
+            304 => Self::KingGalaxian,
+            401 => Self::ClowningAround,
+            402 => Self::BarrelBlast,
+            403 => Self::BarrelDizzy,
+            404 => Self::ClownPrix,
+            501 => Self::PerilousPipes,
+            502 => Self::UnderPressure,
+            503 => Self::DownTheTubes,
+            504 => Self::KromeKeeper,
+            601 => Self::GhostlyGarden,
+            602 => Self::CreepyCatacombs,
+            603 => Self::GraveDanger,
+            604 => Self::TocMansLair,
+            _ => return Err(()),
+        })
+    }
+}
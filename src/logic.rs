@@ -0,0 +1,1023 @@
+//! Splitter decisions. Nothing in here calls into the asr runtime, so this module
+//! also builds and runs natively with `cargo test-host`.
+
+use core::fmt::Write;
+
+use asr::{
+    string::ArrayString,
+    time::Duration,
+    timer::TimerState,
+    watcher::{Pair, Watcher},
+};
+
+use crate::{
+    events::{Event, EventLog},
+    level::{scene, Level, World},
+    memory::{MemoryHealth, MemorySource, PointerStatus},
+    settings::{ResetTrigger, Settings, SplitOn, StartTrigger, TimingMethod},
+    splits::{self, SplitBehavior, SplitEntry},
+    timer::TimerSink,
+    variables::{self, Variables},
+};
+
+/// Raw game values are `None` for ticks where they could not be read
+#[derive(Default)]
+pub struct Watchers {
+    /// Any load source, regardless of settings
+    pub is_loading: Watcher<Option<bool>>,
+    /// `SceneManager.m_bProcessing`
+    pub scene_processing: Watcher<Option<bool>>,
+    /// `GameStateManager.loadScr`
+    pub load_screen: Watcher<Option<bool>>,
+    /// Last level entered, or `None` if no level has been seen since attaching
+    pub level_id: Watcher<Option<Level>>,
+    pub level_id_unfiltered: Watcher<Option<u32>>,
+    pub tocman_qte: Watcher<Option<bool>>,
+    pub stage_time: Watcher<Option<f32>>,
+    pub is_paused: Watcher<Option<bool>>,
+    pub completed_levels_time: Duration,
+    pub levels_completed: u32,
+    /// In-game timing was picked, but the level clock hasn't resolved yet so load removal is used instead
+    pub clock_fallback: bool,
+    /// Removing the pause menu was picked, but the pause flag hasn't resolved yet
+    pub pause_unavailable: bool,
+    /// World splits or skips already done this run, indexed like `World::ALL`
+    pub worlds_split: [bool; World::ALL.len()],
+    pub health: MemoryHealth,
+    pub variables: Variables,
+    pub events: EventLog,
+}
+
+/// Runs a single tick of the splitter against the given game values and timer
+pub fn tick(memory: &impl MemorySource, timer: &mut impl TimerSink, watchers: &mut Watchers, settings: &Settings) {
+    // Splitting logic. Adapted from OG LiveSplit:
+    // Order of execution
+    // 1. update() will always be run first. There are no conditions on the execution of this action.
+    // 2. If the timer is currently either running or paused, then the isLoading, gameTime, and reset actions will be run.
+    // 3. If reset does not return true, then the split action will be run.
+    // 4. If the timer is currently not running (and not paused), then the start action will be run.
+    update_loop(memory, watchers);
+    report_health(timer, &mut watchers.health);
+    report_clock_fallback(timer, watchers, settings);
+    report_pause_unavailable(timer, watchers, settings);
+    variables::publish(timer, watchers);
+
+    let timer_state = timer.state();
+    if timer_state == TimerState::Running || timer_state == TimerState::Paused {
+        if let Some(is_loading) = is_loading(watchers, settings) {
+            if is_loading {
+                timer.pause_game_time()
+            } else {
+                timer.resume_game_time()
+            }
+        }
+
+        if let Some(game_time) = game_time(watchers, settings, memory) {
+            timer.set_game_time(game_time)
+        }
+
+        if let Some(event) = reset(watchers, settings) {
+            timer.reset();
+            record(timer, &mut watchers.events, event);
+            print_events(timer, &watchers.events);
+        } else if let Some(event) = split(watchers, settings) {
+            if let Some(off_route) = off_route(timer.current_split_index(), settings, event) {
+                record(timer, &mut watchers.events, off_route);
+                print_events(timer, &watchers.events);
+            } else {
+                if settings.world_splits {
+                    if let Some(level) = event.level() {
+                        watchers.worlds_split[level.world_index() as usize - 1] = true;
+                    }
+                }
+                if let Event::LevelSkip { .. } = event {
+                    timer.skip_split();
+                } else {
+                    timer.split();
+                }
+                record(timer, &mut watchers.events, event);
+            }
+        }
+    }
+
+    if timer.state() == TimerState::NotRunning {
+        if let Some(event) = start(watchers, settings) {
+            timer.start();
+            timer.pause_game_time();
+            record(timer, &mut watchers.events, event);
+            watchers.completed_levels_time = Duration::ZERO;
+            watchers.levels_completed = 0;
+            watchers.worlds_split = Default::default();
+
+            if let Some(is_loading) = is_loading(watchers, settings) {
+                if is_loading {
+                    timer.pause_game_time()
+                } else {
+                    timer.resume_game_time()
+                }
+            }
+        }
+    }
+}
+
+pub fn update_loop(memory: &impl MemorySource, watchers: &mut Watchers) {
+    // Either load source keeps working on its own if the other one breaks
+    let scene_processing = watchers.health.is_loading.record(memory.is_loading());
+    let load_screen = watchers.health.is_loading_2.record(memory.is_loading_2()).map(|val| val != 0);
+    watchers.scene_processing.update_infallible(scene_processing);
+    watchers.load_screen.update_infallible(load_screen);
+    watchers.is_loading.update_infallible(any_loading([scene_processing, load_screen]));
+
+    let cur_level = watchers.health.level_id.record(memory.level_id());
+
+    watchers.level_id.update_infallible(
+        cur_level
+            .and_then(|val| Level::try_from(val).ok())
+            .or_else(|| watchers.level_id.pair?.current),
+    );
+
+    watchers.level_id_unfiltered.update_infallible(cur_level);
+
+    // Scene-local singletons are destroyed on every scene change or reload
+    if watchers.level_id_unfiltered.pair.is_some_and(|val| val.changed())
+        || watchers.is_loading.pair.is_some_and(|val| val.changed_to(&Some(true)))
+    {
+        memory.leave_scene();
+    }
+
+    // BossTocman only exists in Toc-Man's Lair, so it isn't looked up anywhere else
+    let tocman_qte = if cur_level == Some(Level::TocMansLair as u32) {
+        watchers.health.tocman_qte.record(memory.tocman_qte())
+    } else {
+        None
+    };
+    watchers.tocman_qte.update_infallible(tocman_qte);
+
+    let stage_time = watchers.health.stage_time.record(memory.stage_time());
+    watchers.stage_time.update_infallible(stage_time);
+
+    let is_paused = watchers.health.is_paused.record(memory.is_paused());
+    watchers.is_paused.update_infallible(is_paused);
+
+    if completed_level(watchers).is_some() {
+        watchers.levels_completed += 1;
+
+        // Bank the level clock as it stood on the last tick of the level
+        if let Some(Some(stage_time)) = watchers.stage_time.pair.map(|val| val.old) {
+            watchers.completed_levels_time += Duration::saturating_seconds_f32(stage_time);
+        }
+    }
+}
+
+/// The level that was just completed, on the tick its results screen or ending cutscene comes up
+pub fn completed_level(watchers: &Watchers) -> Option<Level> {
+    let level_id_unfiltered = known(&watchers.level_id_unfiltered)?;
+    let level = Level::try_from(level_id_unfiltered.old).ok()?;
+
+    (level_id_unfiltered.current == scene::RESULTS || scene::is_cutscene(level_id_unfiltered.current)).then_some(level)
+}
+
+/// Loading if any source says so, unknown if none of them could be read
+fn any_loading<const N: usize>(sources: [Option<bool>; N]) -> Option<bool> {
+    if sources.contains(&Some(true)) {
+        Some(true)
+    } else if sources.contains(&Some(false)) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// The watcher's values, but only if both the old and the current one were read successfully.
+/// Transitions from or to a failed read are never reported as changes.
+fn known<T: Copy>(watcher: &Watcher<Option<T>>) -> Option<Pair<T>> {
+    let pair = watcher.pair?;
+    Some(Pair {
+        old: pair.old?,
+        current: pair.current?,
+    })
+}
+
+/// Publishes pointer status changes as timer variables and log messages
+fn report_health(timer: &mut impl TimerSink, health: &mut MemoryHealth) {
+    for (name, pointer) in health.pointers_mut() {
+        let Some(status) = pointer.take_change() else { continue };
+
+        let mut key = ArrayString::<32>::new();
+        let _ = write!(key, "Pointer {name}");
+        timer.set_variable(&key, status.name());
+
+        let mut message = ArrayString::<64>::new();
+        let _ = write!(message, "Pointer {name} is {}", status.name());
+        timer.print_message(&message);
+    }
+}
+
+/// Tells the runner whenever in-game timing falls back to load removal, or stops doing so
+fn report_clock_fallback(timer: &mut impl TimerSink, watchers: &mut Watchers, settings: &Settings) {
+    let fallback = settings.timing_method == TimingMethod::InGame && !uses_level_clock(watchers, settings);
+    if fallback == watchers.clock_fallback {
+        return;
+    }
+
+    watchers.clock_fallback = fallback;
+    timer.set_variable("Level clock", if fallback { "not found, using load removal" } else { "in use" });
+    timer.print_message(if fallback {
+        "Level clock pointer has not resolved, using load removal instead of the in-game clock"
+    } else {
+        "Level clock pointer resolved, using the in-game clock"
+    });
+}
+
+/// Tells the runner whenever removing the pause menu can't work because the pause flag can't be read, or starts working
+fn report_pause_unavailable(timer: &mut impl TimerSink, watchers: &mut Watchers, settings: &Settings) {
+    let unavailable = settings.load_pause
+        && !matches!(watchers.health.is_paused.status(), PointerStatus::Resolved | PointerStatus::Failing);
+    if unavailable == watchers.pause_unavailable {
+        return;
+    }
+
+    watchers.pause_unavailable = unavailable;
+    timer.set_variable("Pause menu", if unavailable { "not found, pause time not removed" } else { "detected" });
+    timer.print_message(if unavailable {
+        "Pause flag pointer has not resolved, time in the pause menu is not removed"
+    } else {
+        "Pause flag pointer resolved, removing time in the pause menu"
+    });
+}
+
+/// Logs the event and keeps it in the event log. Splits also update the "Last split reason" variable.
+fn record(timer: &mut impl TimerSink, events: &mut EventLog, event: Event) {
+    let mut message = ArrayString::<64>::new();
+    let _ = write!(message, "{event}");
+
+    timer.print_message(&message);
+    if event.is_split() {
+        timer.set_variable("Last split reason", &message);
+    }
+
+    events.push(event);
+}
+
+/// Prints the recent events, newest first, so an unexpected reset or held back split comes with its history
+fn print_events(timer: &mut impl TimerSink, events: &EventLog) {
+    timer.print_message("Recent events, newest first:");
+    for event in events.iter() {
+        let mut message = ArrayString::<64>::new();
+        let _ = write!(message, "  {event}");
+        timer.print_message(&message);
+    }
+}
+
+pub fn start(watchers: &Watchers, settings: &Settings) -> Option<Event> {
+    if !settings.start {
+        return None;
+    }
+
+    let level_id_unfiltered = known(&watchers.level_id_unfiltered)?;
+    let is_loading = known(&watchers.is_loading)?;
+
+    if settings.il_mode || settings.start_trigger == StartTrigger::LevelEntry {
+        // Start once the level is playable, either after entering it or after a restart reloads it
+        let level = Level::try_from(level_id_unfiltered.current).ok()?;
+        let ready = !is_loading.current && (level_id_unfiltered.changed() || is_loading.changed());
+
+        return (ready && level_enabled(settings, level)).then_some(Event::LevelStart { level });
+    }
+
+    (level_id_unfiltered.current == scene::NEW_GAME && is_loading.changed_to(&true))
+        .then_some(Event::Start { scene: level_id_unfiltered.current })
+}
+
+pub fn split(watchers: &Watchers, settings: &Settings) -> Option<Event> {
+    let level_id_unfiltered = known(&watchers.level_id_unfiltered)?;
+    // Attaching mid-session leaves no level to split for until one is actually entered
+    let level = watchers.level_id.pair?.current?;
+    let entry = SplitEntry::find(level)?;
+
+    if entry.behavior == SplitBehavior::BossQte
+        && level_id_unfiltered.current == entry.level as u32
+        && !level_id_unfiltered.changed()
+        && known(&watchers.tocman_qte).is_some_and(|val| val.changed_to(&true))
+    {
+        if settings.world_splits && !settings.il_mode {
+            return world_complete(watchers, settings, level);
+        }
+        return split_or_skip(settings, entry, Event::BossQte { level });
+    }
+
+    if settings.il_mode {
+        // The final QTE above already ends Toc-Man's Lair, before its ending cutscene comes up
+        return (entry.behavior == SplitBehavior::Exit && completed_level(watchers) == Some(level))
+            .then_some(Event::LevelComplete { level });
+    }
+
+    let exited = level_id_unfiltered.changed_to(&scene::HUB)
+        && (level_id_unfiltered.old == scene::RESULTS || scene::is_cutscene(level_id_unfiltered.old));
+    let entered = level_id_unfiltered.old == scene::HUB && level_id_unfiltered.current == level as u32;
+
+    if settings.world_splits {
+        return if exited { world_complete(watchers, settings, level) } else { None };
+    }
+
+    if exited && settings.split_on != SplitOn::Entry {
+        split_or_skip(settings, entry, Event::LevelExit { level, old: level_id_unfiltered.old })
+    } else if entered && settings.split_on != SplitOn::Exit {
+        split_or_skip(settings, entry, Event::LevelEntry { level })
+    } else {
+        None
+    }
+}
+
+/// `split` for an enabled level, otherwise a skip if disabled levels are skipped
+fn split_or_skip(settings: &Settings, entry: &SplitEntry, split: Event) -> Option<Event> {
+    if entry.enabled(settings) {
+        Some(split)
+    } else {
+        settings.skip_disabled.then_some(Event::LevelSkip { level: entry.level })
+    }
+}
+
+/// World splits: `level` was just completed, split if it closes its world and that world hasn't been split yet.
+/// A disabled world is skipped instead when disabled levels are skipped.
+fn world_complete(watchers: &Watchers, settings: &Settings, level: Level) -> Option<Event> {
+    let world = level.world();
+    if watchers.worlds_split[world.index() as usize - 1] || splits::world_closing_level(settings, world) != Some(level) {
+        return None;
+    }
+
+    if settings.world_enabled(world) {
+        Some(Event::WorldComplete { world, level })
+    } else {
+        settings.skip_disabled.then_some(Event::LevelSkip { level })
+    }
+}
+
+pub fn reset(watchers: &Watchers, settings: &Settings) -> Option<Event> {
+    let level_id_unfiltered = known(&watchers.level_id_unfiltered)?;
+
+    if settings.il_mode {
+        // Quitting or restarting the level always resets in IL mode
+        let level = Level::try_from(level_id_unfiltered.old).ok()?;
+
+        return if level_id_unfiltered.changed() {
+            completed_level(watchers).is_none().then_some(Event::LevelQuit { level, scene: level_id_unfiltered.current })
+        } else {
+            known(&watchers.is_loading)
+                .is_some_and(|val| val.changed_to(&true))
+                .then_some(Event::LevelRestart { level })
+        };
+    }
+
+    if !settings.reset {
+        return None;
+    }
+
+    let triggered = match settings.reset_trigger {
+        ResetTrigger::TitleScreen => level_id_unfiltered.changed_to(&scene::TITLE_SCREEN),
+        ResetTrigger::NewGame => level_id_unfiltered.changed_to(&scene::NEW_GAME),
+        ResetTrigger::Both => {
+            level_id_unfiltered.changed_to(&scene::TITLE_SCREEN) || level_id_unfiltered.changed_to(&scene::NEW_GAME)
+        }
+    };
+
+    triggered.then_some(Event::Reset { scene: level_id_unfiltered.current })
+}
+
+/// Route splits: replaces `event` with `Event::OffRoute` if its level isn't the one the current segment expects
+fn off_route(split_index: Option<u64>, settings: &Settings, event: Event) -> Option<Event> {
+    // A single level has no route to follow
+    if !settings.route_splits || settings.il_mode {
+        return None;
+    }
+
+    let level = event.level()?;
+    let expected = splits::route_level(settings, split_index?);
+
+    (expected != Some(level)).then_some(Event::OffRoute { level, expected })
+}
+
+fn level_enabled(settings: &Settings, level: Level) -> bool {
+    SplitEntry::find(level).is_some_and(|entry| entry.enabled(settings))
+}
+
+/// In-game timing, unless the level clock has never been read successfully.
+/// A clock that only fails outside of levels keeps being used.
+fn uses_level_clock(watchers: &Watchers, settings: &Settings) -> bool {
+    settings.timing_method == TimingMethod::InGame
+        && matches!(watchers.health.stage_time.status(), PointerStatus::Resolved | PointerStatus::Failing)
+}
+
+pub fn is_loading(watchers: &Watchers, settings: &Settings) -> Option<bool> {
+    // When using the level clock, game time is entirely driven by game_time()
+    if uses_level_clock(watchers, settings) {
+        return Some(true);
+    }
+
+    // Disabled sources count as not loading
+    let source = |enabled: bool, watcher: &Watcher<Option<bool>>| {
+        if enabled {
+            watcher.pair.and_then(|val| val.current)
+        } else {
+            Some(false)
+        }
+    };
+
+    let results = if settings.load_results {
+        watchers.level_id_unfiltered.pair.and_then(|val| val.current).map(|val| val == scene::RESULTS)
+    } else {
+        Some(false)
+    };
+
+    any_loading([
+        source(settings.load_scene_processing, &watchers.scene_processing),
+        source(settings.load_screen, &watchers.load_screen),
+        source(settings.load_pause, &watchers.is_paused),
+        results,
+    ])
+}
+
+pub fn game_time(watchers: &Watchers, settings: &Settings, _memory: &impl MemorySource) -> Option<Duration> {
+    if !uses_level_clock(watchers, settings) {
+        return None;
+    }
+
+    let level_id_unfiltered = watchers.level_id_unfiltered.pair?.current?;
+
+    let current_level_time = if Level::try_from(level_id_unfiltered).is_ok() {
+        Duration::saturating_seconds_f32(watchers.stage_time.pair?.current?)
+    } else {
+        Duration::ZERO
+    };
+
+    Some(watchers.completed_levels_time + current_level_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        memory::{Frame, ScriptedMemory},
+        timer::{RecordingTimer, TimerEvent},
+    };
+
+    /// A scripted game session. Every step changes the game values, runs one tick and returns the timer calls it made.
+    struct Session {
+        memory: ScriptedMemory,
+        timer: RecordingTimer,
+        watchers: Watchers,
+        settings: Settings,
+    }
+
+    impl Session {
+        /// Attached while in `scene`, with nothing loading
+        fn new(settings: Settings, scene: u32) -> Self {
+            Self::with_frame(settings, Self::frame(scene))
+        }
+
+        /// In `scene` with nothing loading. The scene-local and level clock pointers aren't readable.
+        fn frame(scene: u32) -> Frame {
+            Frame {
+                is_loading: Some(false),
+                is_loading_2: Some(0),
+                level_id: Some(scene),
+                tocman_qte: None,
+                stage_time: None,
+                is_paused: Some(false),
+            }
+        }
+
+        /// Attached with the given game values
+        fn with_frame(settings: Settings, frame: Frame) -> Self {
+            let mut session = Self {
+                memory: ScriptedMemory { frame },
+                timer: RecordingTimer::default(),
+                watchers: Watchers::default(),
+                settings,
+            };
+            session.tick();
+            session
+        }
+
+        /// Same as `new()`, with the timer already running
+        fn running(settings: Settings, scene: u32) -> Self {
+            let mut session = Self::new(settings, scene);
+            session.timer.start();
+            session.timer.events.clear();
+            session
+        }
+
+        fn tick(&mut self) -> Vec<TimerEvent> {
+            tick(&self.memory, &mut self.timer, &mut self.watchers, &self.settings);
+            std::mem::take(&mut self.timer.events)
+        }
+
+        fn scene(&mut self, scene: u32) -> Vec<TimerEvent> {
+            self.memory.frame.level_id = Some(scene);
+            self.tick()
+        }
+
+        fn loading(&mut self, is_loading: bool) -> Vec<TimerEvent> {
+            self.memory.frame.is_loading = Some(is_loading);
+            self.tick()
+        }
+
+        fn tocman_qte(&mut self, success: bool) -> Vec<TimerEvent> {
+            self.memory.frame.tocman_qte = Some(success);
+            self.tick()
+        }
+
+        /// Enters the level from the hub and leaves it through the results screen, returning the timer calls made
+        /// on each of those ticks
+        fn play(&mut self, level: Level) -> Vec<TimerEvent> {
+            let mut events = self.scene(level as u32);
+            events.extend(self.scene(scene::RESULTS));
+            events.extend(self.scene(scene::HUB));
+            events
+        }
+
+        fn last_event(&self) -> Option<Event> {
+            self.watchers.events.iter().next()
+        }
+
+        /// Checks that the last step was held back as off route
+        fn assert_off_route(&self, events: &[TimerEvent], level: Level, expected: Level) {
+            assert!(!events.contains(&TimerEvent::Split) && !events.contains(&TimerEvent::SkipSplit), "{level:?} split");
+            assert_eq!(self.last_event(), Some(Event::OffRoute { level, expected: Some(expected) }));
+        }
+    }
+
+    #[test]
+    fn in_game_timing_falls_back_until_level_clock_resolves() {
+        use TimerEvent::*;
+
+        let mut settings = Settings::default();
+        settings.timing_method = TimingMethod::InGame;
+        let mut session = Session::running(settings, scene::HUB);
+
+        assert_eq!(session.scene(Level::BuccaneerBeach as u32), [ResumeGameTime]);
+        assert_eq!(session.timer.variables["Level clock"], "not found, using load removal");
+
+        session.memory.frame.stage_time = Some(1.5);
+        assert_eq!(session.tick(), [PauseGameTime, SetGameTime(Duration::seconds_f32(1.5))]);
+        assert_eq!(session.timer.variables["Level clock"], "in use");
+    }
+
+    #[test]
+    fn tocman_qte_pointer_is_not_needed_outside_its_level() {
+        let mut session = Session::new(Settings::default(), scene::HUB);
+        assert_eq!(session.timer.variables["Pointer tocman_qte"], "not needed yet");
+        assert_eq!(session.timer.variables["Pointer level_id"], "resolved");
+
+        session.scene(Level::TocMansLair as u32);
+        assert_eq!(session.timer.variables["Pointer tocman_qte"], "never resolved");
+        session.tocman_qte(false);
+        assert_eq!(session.timer.variables["Pointer tocman_qte"], "resolved");
+    }
+
+    #[test]
+    fn unresolved_pause_flag_is_reported() {
+        let mut settings = Settings::default();
+        settings.load_pause = true;
+        let frame = Frame { is_paused: None, ..Session::frame(scene::HUB) };
+        let mut session = Session::with_frame(settings, frame);
+        assert_eq!(session.timer.variables["Pause menu"], "not found, pause time not removed");
+
+        session.memory.frame.is_paused = Some(false);
+        session.tick();
+        assert_eq!(session.timer.variables["Pause menu"], "detected");
+    }
+
+    #[test]
+    fn every_level_splits_on_exit() {
+        for level in Level::ALL {
+            let mut session = Session::running(Settings::default(), scene::HUB);
+
+            assert!(!session.scene(level as u32).contains(&TimerEvent::Split), "{level:?} split on entry");
+            assert!(!session.scene(scene::RESULTS).contains(&TimerEvent::Split), "{level:?} split on results");
+            assert!(session.scene(scene::HUB).contains(&TimerEvent::Split), "{level:?} did not split on exit");
+            assert!(!session.tick().contains(&TimerEvent::Split), "{level:?} split twice");
+        }
+    }
+
+    #[test]
+    fn disabled_level_does_not_split() {
+        let mut settings = Settings::default();
+        settings.crisis_cavern = false;
+        let mut session = Session::running(settings, scene::HUB);
+
+        session.scene(Level::CrisisCavern as u32);
+        session.scene(scene::RESULTS);
+        assert!(!session.scene(scene::HUB).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn split_on_entry_splits_entering_from_hub() {
+        let mut settings = Settings::default();
+        settings.split_on = SplitOn::Entry;
+        let mut session = Session::running(settings, scene::HUB);
+
+        assert!(session.scene(Level::FarOut as u32).contains(&TimerEvent::Split));
+        assert_eq!(session.last_event(), Some(Event::LevelEntry { level: Level::FarOut }));
+        assert!(!session.tick().contains(&TimerEvent::Split));
+        assert!(!session.scene(scene::RESULTS).contains(&TimerEvent::Split));
+        assert!(!session.scene(scene::HUB).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn split_on_entry_ignores_other_scenes() {
+        let mut settings = Settings::default();
+        settings.split_on = SplitOn::Entry;
+        let mut session = Session::running(settings, scene::NEW_GAME);
+
+        assert!(!session.scene(Level::BuccaneerBeach as u32).contains(&TimerEvent::Split));
+        assert!(!session.scene(1001).contains(&TimerEvent::Split));
+        assert!(!session.scene(Level::BuccaneerBeach as u32).contains(&TimerEvent::Split));
+        assert!(!session.scene(scene::TITLE_SCREEN).contains(&TimerEvent::Split));
+        assert!(!session.scene(Level::BuccaneerBeach as u32).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn split_on_both_splits_entering_and_leaving() {
+        let mut settings = Settings::default();
+        settings.split_on = SplitOn::Both;
+        let mut session = Session::running(settings, scene::HUB);
+
+        assert!(session.scene(Level::FarOut as u32).contains(&TimerEvent::Split));
+        assert!(!session.scene(scene::RESULTS).contains(&TimerEvent::Split));
+        assert!(session.scene(scene::HUB).contains(&TimerEvent::Split));
+        assert_eq!(session.timer.split_index, Some(2));
+    }
+
+    #[test]
+    fn disabled_level_skips_on_exit() {
+        let mut settings = Settings::default();
+        settings.skip_disabled = true;
+        settings.crisis_cavern = false;
+        let mut session = Session::running(settings, scene::HUB);
+
+        let events = session.play(Level::CrisisCavern);
+        assert!(events.contains(&TimerEvent::SkipSplit));
+        assert!(!events.contains(&TimerEvent::Split));
+        assert!(session.play(Level::ManicMines).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn disabled_level_skips_on_entry() {
+        let mut settings = Settings::default();
+        settings.skip_disabled = true;
+        settings.split_on = SplitOn::Entry;
+        settings.crisis_cavern = false;
+        let mut session = Session::running(settings, scene::HUB);
+
+        assert!(session.scene(Level::CrisisCavern as u32).contains(&TimerEvent::SkipSplit));
+        session.scene(scene::RESULTS);
+        assert!(!session.scene(scene::HUB).contains(&TimerEvent::SkipSplit));
+        assert!(session.scene(Level::ManicMines as u32).contains(&TimerEvent::Split));
+    }
+
+    fn world_session(settings: impl FnOnce(&mut Settings)) -> Session {
+        let mut world_settings = Settings::default();
+        world_settings.world_splits = true;
+        settings(&mut world_settings);
+        Session::running(world_settings, scene::HUB)
+    }
+
+    #[test]
+    fn world_splits_on_boss_exit() {
+        let mut session = world_session(|_| ());
+
+        for level in [Level::BuccaneerBeach, Level::CorsairsCove, Level::CrazyCannonade] {
+            assert!(!session.play(level).contains(&TimerEvent::Split), "{level:?} split");
+        }
+        assert!(session.play(Level::HmsWindbag).contains(&TimerEvent::Split));
+        assert_eq!(session.last_event(), Some(Event::WorldComplete { world: World::Pirate, level: Level::HmsWindbag }));
+    }
+
+    #[test]
+    fn world_splits_on_last_enabled_level() {
+        let mut session = world_session(|settings| settings.hms_windbag = false);
+
+        assert!(!session.play(Level::CorsairsCove).contains(&TimerEvent::Split));
+        assert!(session.play(Level::CrazyCannonade).contains(&TimerEvent::Split));
+        assert!(!session.play(Level::HmsWindbag).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn world_splits_once() {
+        let mut session = world_session(|_| ());
+
+        assert!(session.play(Level::AnubisRex).contains(&TimerEvent::Split));
+        assert!(!session.play(Level::AnubisRex).contains(&TimerEvent::Split));
+        assert!(!session.play(Level::ManicMines).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn tocman_qte_closes_haunted() {
+        let mut session = world_session(|_| ());
+        session.memory.frame.tocman_qte = Some(false);
+
+        assert!(!session.scene(Level::TocMansLair as u32).contains(&TimerEvent::Split));
+        assert!(session.tocman_qte(true).contains(&TimerEvent::Split));
+        assert_eq!(session.last_event(), Some(Event::WorldComplete { world: World::Haunted, level: Level::TocMansLair }));
+    }
+
+    #[test]
+    fn disabled_world_skips_once() {
+        let mut settings = Settings::default();
+        settings.world_splits = true;
+        settings.skip_disabled = true;
+        settings.ruins_world = false;
+        let mut session = Session::running(settings, scene::HUB);
+
+        for level in [Level::BuccaneerBeach, Level::CorsairsCove, Level::CrazyCannonade] {
+            assert!(!session.play(level).contains(&TimerEvent::Split), "{level:?} split");
+        }
+        assert!(session.play(Level::HmsWindbag).contains(&TimerEvent::Split));
+        assert!(!session.play(Level::CrisisCavern).contains(&TimerEvent::SkipSplit));
+        assert!(session.play(Level::AnubisRex).contains(&TimerEvent::SkipSplit));
+        assert!(!session.play(Level::AnubisRex).contains(&TimerEvent::SkipSplit));
+    }
+
+    #[test]
+    fn tocman_qte_splits_without_scene_change() {
+        let mut session = Session::running(Settings::default(), scene::HUB);
+        session.memory.frame.tocman_qte = Some(false);
+
+        assert!(!session.scene(Level::TocMansLair as u32).contains(&TimerEvent::Split));
+        assert!(!session.tick().contains(&TimerEvent::Split));
+        assert!(session.tocman_qte(true).contains(&TimerEvent::Split));
+        assert!(!session.tick().contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn tocman_qte_read_failure_does_not_split() {
+        let mut session = Session::running(Settings::default(), scene::HUB);
+        session.memory.frame.tocman_qte = None;
+
+        session.scene(Level::TocMansLair as u32);
+        assert!(!session.tocman_qte(true).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn results_screen_runs_without_load_results() {
+        use TimerEvent::*;
+
+        let mut session = Session::running(Settings::default(), scene::HUB);
+
+        assert_eq!(session.scene(Level::AnubisRex as u32), [ResumeGameTime]);
+        assert_eq!(session.scene(scene::RESULTS), [ResumeGameTime]);
+        assert_eq!(session.scene(scene::HUB), [ResumeGameTime, Split]);
+    }
+
+    #[test]
+    fn results_screen_pauses_with_load_results() {
+        use TimerEvent::*;
+
+        let mut settings = Settings::default();
+        settings.load_results = true;
+        let mut session = Session::running(settings, scene::HUB);
+
+        assert_eq!(session.scene(Level::AnubisRex as u32), [ResumeGameTime]);
+        assert_eq!(session.scene(scene::RESULTS), [PauseGameTime]);
+        assert_eq!(session.tick(), [PauseGameTime]);
+        assert_eq!(session.scene(scene::HUB), [ResumeGameTime, Split]);
+
+        // Boss cutscenes aren't results screens
+        assert_eq!(session.scene(Level::HmsWindbag as u32), [ResumeGameTime]);
+        assert_eq!(session.scene(1001), [ResumeGameTime]);
+    }
+
+    fn il_session() -> Session {
+        let mut settings = Settings::default();
+        settings.il_mode = true;
+        Session::new(settings, scene::HUB)
+    }
+
+    #[test]
+    fn il_entering_level_starts() {
+        let mut session = il_session();
+        let level = Level::ManicMines;
+
+        assert!(session.scene(level as u32).contains(&TimerEvent::Start));
+        assert_eq!(session.last_event(), Some(Event::LevelStart { level }));
+    }
+
+    #[test]
+    fn il_results_split() {
+        let mut session = il_session();
+        let level = Level::ManicMines;
+
+        session.scene(level as u32);
+        assert!(session.scene(scene::RESULTS).contains(&TimerEvent::Split));
+        assert_eq!(session.last_event(), Some(Event::LevelComplete { level }));
+    }
+
+    #[test]
+    fn il_restart_resets_and_starts_again() {
+        let mut session = il_session();
+        let level = Level::ManicMines;
+
+        session.scene(level as u32);
+        assert!(session.loading(true).contains(&TimerEvent::Reset));
+        assert_eq!(session.last_event(), Some(Event::LevelRestart { level }));
+        assert!(session.loading(false).contains(&TimerEvent::Start));
+    }
+
+    #[test]
+    fn il_quit_resets() {
+        for quit_to in [scene::HUB, scene::TITLE_SCREEN] {
+            let mut session = il_session();
+            let level = Level::ManicMines;
+
+            session.scene(level as u32);
+            assert!(session.scene(quit_to).contains(&TimerEvent::Reset));
+            assert_eq!(session.last_event(), Some(Event::LevelQuit { level, scene: quit_to }));
+        }
+    }
+
+    #[test]
+    fn il_leaving_results_does_not_reset() {
+        let mut session = il_session();
+
+        session.scene(Level::ManicMines as u32);
+        session.scene(scene::RESULTS);
+        let events = session.scene(scene::HUB);
+        assert!(!events.contains(&TimerEvent::Reset));
+        assert!(!events.contains(&TimerEvent::Split));
+    }
+
+    fn route_session(settings: impl FnOnce(&mut Settings)) -> Session {
+        let mut route_settings = Settings::default();
+        route_settings.route_splits = true;
+        settings(&mut route_settings);
+        Session::running(route_settings, scene::HUB)
+    }
+
+    #[test]
+    fn route_on_exit() {
+        let mut session = route_session(|_| ());
+
+        assert!(session.play(Level::BuccaneerBeach).contains(&TimerEvent::Split));
+        let events = session.play(Level::BuccaneerBeach);
+        session.assert_off_route(&events, Level::BuccaneerBeach, Level::CorsairsCove);
+        let events = session.play(Level::CrazyCannonade);
+        session.assert_off_route(&events, Level::CrazyCannonade, Level::CorsairsCove);
+        assert!(session.play(Level::CorsairsCove).contains(&TimerEvent::Split));
+        assert_eq!(session.timer.split_index, Some(2));
+    }
+
+    #[test]
+    fn route_on_entry() {
+        let mut session = route_session(|settings| settings.split_on = SplitOn::Entry);
+
+        assert!(session.play(Level::BuccaneerBeach).contains(&TimerEvent::Split));
+        let events = session.play(Level::BuccaneerBeach);
+        session.assert_off_route(&events, Level::BuccaneerBeach, Level::CorsairsCove);
+        assert!(session.play(Level::CorsairsCove).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn route_on_entry_ends_with_final_qte() {
+        let mut session = route_session(|settings| {
+            settings.split_on = SplitOn::Entry;
+            settings.pirate_world = false;
+            settings.ruins_world = false;
+            settings.space_world = false;
+            settings.funhouse_world = false;
+            settings.mill_world = false;
+        });
+
+        for level in [Level::GhostlyGarden, Level::CreepyCatacombs, Level::GraveDanger] {
+            assert!(session.play(level).contains(&TimerEvent::Split), "{level:?} did not split");
+        }
+        session.memory.frame.tocman_qte = Some(false);
+        assert!(session.scene(Level::TocMansLair as u32).contains(&TimerEvent::Split));
+        assert!(session.tocman_qte(true).contains(&TimerEvent::Split));
+        assert_eq!(session.timer.split_index, Some(5));
+    }
+
+    #[test]
+    fn route_on_both() {
+        let mut session = route_session(|settings| settings.split_on = SplitOn::Both);
+
+        assert_eq!(session.play(Level::BuccaneerBeach).iter().filter(|&&val| val == TimerEvent::Split).count(), 2);
+        let events = session.scene(Level::BuccaneerBeach as u32);
+        session.assert_off_route(&events, Level::BuccaneerBeach, Level::CorsairsCove);
+        session.scene(scene::RESULTS);
+        let events = session.scene(scene::HUB);
+        session.assert_off_route(&events, Level::BuccaneerBeach, Level::CorsairsCove);
+        assert_eq!(session.play(Level::CorsairsCove).iter().filter(|&&val| val == TimerEvent::Split).count(), 2);
+    }
+
+    #[test]
+    fn route_with_world_splits() {
+        let mut session = route_session(|settings| settings.world_splits = true);
+
+        assert!(session.play(Level::HmsWindbag).contains(&TimerEvent::Split));
+        let events = session.play(Level::KingGalaxian);
+        session.assert_off_route(&events, Level::KingGalaxian, Level::AnubisRex);
+        assert!(session.play(Level::AnubisRex).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn route_with_skipped_levels() {
+        let mut session = route_session(|settings| {
+            settings.skip_disabled = true;
+            settings.corsair_cove = false;
+        });
+
+        assert!(session.play(Level::BuccaneerBeach).contains(&TimerEvent::Split));
+        assert!(session.play(Level::CorsairsCove).contains(&TimerEvent::SkipSplit));
+        let events = session.play(Level::CorsairsCove);
+        session.assert_off_route(&events, Level::CorsairsCove, Level::CrazyCannonade);
+        assert!(session.play(Level::CrazyCannonade).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn attaching_mid_session_does_not_split() {
+        let mut session = Session::running(Settings::default(), 1001);
+        assert!(!session.scene(scene::HUB).contains(&TimerEvent::Split));
+
+        let mut session = Session::running(Settings::default(), scene::HUB);
+        assert!(!session.scene(1001).contains(&TimerEvent::Split));
+        assert!(!session.scene(scene::HUB).contains(&TimerEvent::Split));
+    }
+
+    #[test]
+    fn new_game_loading_starts() {
+        let mut session = Session::new(Settings::default(), scene::TITLE_SCREEN);
+
+        assert!(!session.scene(scene::NEW_GAME).contains(&TimerEvent::Start));
+        assert!(session.loading(true).contains(&TimerEvent::Start));
+        assert_eq!(session.timer.state, TimerState::Running);
+    }
+
+    #[test]
+    fn start_pauses_game_time_before_checking_loads() {
+        let mut session = Session::new(Settings::default(), scene::NEW_GAME);
+        assert_eq!(session.loading(true), [TimerEvent::Start, TimerEvent::PauseGameTime, TimerEvent::PauseGameTime]);
+
+        let mut settings = Settings::default();
+        settings.start_trigger = StartTrigger::LevelEntry;
+        let mut session = Session::new(settings, scene::HUB);
+        assert_eq!(
+            session.scene(Level::BuccaneerBeach as u32),
+            [TimerEvent::Start, TimerEvent::PauseGameTime, TimerEvent::ResumeGameTime]
+        );
+    }
+
+    #[test]
+    fn session_timer_call_order() {
+        use TimerEvent::*;
+
+        let mut settings = Settings::default();
+        settings.reset = true;
+        let mut session = Session::new(settings, scene::TITLE_SCREEN);
+
+        assert!(session.scene(scene::NEW_GAME).is_empty());
+        assert_eq!(session.loading(true), [Start, PauseGameTime, PauseGameTime]);
+        assert_eq!(session.loading(false), [ResumeGameTime]);
+        assert_eq!(session.scene(scene::HUB), [ResumeGameTime]);
+        assert_eq!(session.scene(Level::BuccaneerBeach as u32), [ResumeGameTime]);
+        assert_eq!(session.loading(true), [PauseGameTime]);
+        assert_eq!(session.loading(false), [ResumeGameTime]);
+        assert_eq!(session.scene(scene::RESULTS), [ResumeGameTime]);
+        assert_eq!(session.scene(scene::HUB), [ResumeGameTime, Split]);
+        assert_eq!(session.scene(scene::TITLE_SCREEN), [ResumeGameTime]);
+        // Reset is checked before split, and start doesn't fire again without a loading edge
+        assert_eq!(session.scene(scene::NEW_GAME), [ResumeGameTime, Reset]);
+        assert!(session.tick().is_empty());
+        assert_eq!(session.loading(true), [Start, PauseGameTime, PauseGameTime]);
+    }
+
+    #[test]
+    fn reset_prints_recent_events() {
+        let mut settings = Settings::default();
+        settings.reset = true;
+        let mut session = Session::new(settings, scene::NEW_GAME);
+
+        session.loading(true);
+        session.scene(scene::HUB);
+        session.timer.messages.clear();
+        session.scene(scene::NEW_GAME);
+
+        assert_eq!(
+            session.timer.messages,
+            [
+                "reset: scene changed to 4",
+                "Recent events, newest first:",
+                "  reset: scene changed to 4",
+                "  start: scene 4 + loading rising edge",
+            ]
+        );
+    }
+
+    #[test]
+    fn loading_outside_new_game_does_not_start() {
+        let mut session = Session::new(Settings::default(), scene::HUB);
+
+        assert!(!session.loading(true).contains(&TimerEvent::Start));
+        assert_eq!(session.timer.state, TimerState::NotRunning);
+    }
+}
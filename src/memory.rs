@@ -0,0 +1,336 @@
+use core::{cell::Cell, fmt::Write};
+
+use asr::{
+    game_engine::unity::il2cpp::{Image, Module, UnityPointer, Version},
+    string::ArrayString,
+    Address, Process,
+};
+
+use crate::{
+    timer::TimerSink,
+    version::{self, GameVersion, PROFILES},
+};
+
+/// Logical game values read by `update_loop()`, regardless of where they come from.
+/// `None` means the value could not be read this tick.
+pub trait MemorySource {
+    /// `SceneManager.m_bProcessing`
+    fn is_loading(&self) -> Option<bool>;
+    /// `GameStateManager.loadScr`, non-zero while a loading screen is shown
+    fn is_loading_2(&self) -> Option<u64>;
+    /// `SceneManager.m_eCurrentScene`
+    fn level_id(&self) -> Option<u32>;
+    /// `BossTocman.m_qteSuccess`, only looked up while in Toc-Man's Lair
+    fn tocman_qte(&self) -> Option<bool>;
+    /// Level clock, in seconds
+    fn stage_time(&self) -> Option<f32>;
+    /// Whether the pause menu is open
+    fn is_paused(&self) -> Option<bool>;
+    /// Drops cached scene-local instances. Called whenever the scene changes or reloads.
+    fn leave_scene(&self) {}
+}
+
+/// il2cpp metadata versions to try, most likely first
+const IL2CPP_VERSIONS: [(Version, &str); 4] = [
+    (Version::V2020, "2020"),
+    (Version::V2022, "2022"),
+    (Version::V2019, "2019"),
+    (Version::Base, "base"),
+];
+
+/// Assembly to look up by name if the default image can't be found
+const GAME_ASSEMBLY: &str = "Assembly-CSharp";
+
+/// il2cpp module and game image, looked up once per game launch
+pub struct Il2cpp {
+    module: Module,
+    image: Image,
+    version: Version,
+}
+
+impl Il2cpp {
+    /// Tries every il2cpp version in turn, starting with `preferred`, the one that worked last time.
+    /// Attaching doesn't check the metadata layout, so a version is only kept once the game image
+    /// and the classes used by the pointer profiles can be found with it.
+    pub fn attach(game: &Process, preferred: Option<Version>, timer: &mut impl TimerSink) -> Option<Self> {
+        let (module, version, name, image, image_name) = IL2CPP_VERSIONS
+            .iter()
+            .filter(|&&(version, _)| Some(version) == preferred)
+            .chain(IL2CPP_VERSIONS.iter().filter(|&&(version, _)| Some(version) != preferred))
+            .find_map(|&(version, name)| {
+                let module = Module::attach(game, version)?;
+                let (image, image_name) = Self::find_image(game, &module)?;
+                Some((module, version, name, image, image_name))
+            })?;
+
+        let mut message = ArrayString::<64>::new();
+        let _ = write!(message, "Attached to il2cpp {name} ({image_name})");
+        timer.print_message(&message);
+
+        Some(Self { module, image, version })
+    }
+
+    /// il2cpp version the metadata was read with
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Default image, or the game assembly looked up by name, as long as it has the scene manager class
+    fn find_image(game: &Process, module: &Module) -> Option<(Image, &'static str)> {
+        let has_profile_class = |image: &Image| {
+            PROFILES.iter().any(|profile| image.get_class(game, module, profile.level_id.class).is_some())
+        };
+
+        module
+            .get_default_image(game)
+            .filter(has_profile_class)
+            .map(|image| (image, "default image"))
+            .or_else(|| Some((module.get_image(game, GAME_ASSEMBLY).filter(has_profile_class)?, GAME_ASSEMBLY)))
+    }
+}
+
+/// Addresses resolved through il2cpp on the running game
+pub struct Memory<'a> {
+    game: &'a Process,
+    il2cpp: &'a Il2cpp,
+    version: GameVersion,
+    build_id: Option<u32>,
+    is_loading: UnityPointer<2>,
+    level_id: UnityPointer<2>,
+    is_loading_2: UnityPointer<2>,
+    tocman_qte: LazyPointer,
+    stage_time: UnityPointer<2>,
+    is_paused: UnityPointer<2>,
+}
+
+impl<'a> Memory<'a> {
+    /// Picks the pointer profile and checks it against the game. Returns `None` until the scene manager exists.
+    pub fn init(game: &'a Process, il2cpp: &'a Il2cpp, timer: &mut impl TimerSink) -> Option<Self> {
+        // Pick the first profile whose scene pointer resolves on the running build
+        let (profile, level_id) = PROFILES.iter().find_map(|profile| {
+            let level_id = profile.level_id.pointer();
+            level_id.deref::<u32>(game, &il2cpp.module, &il2cpp.image).ok()?;
+            Some((profile, level_id))
+        })?;
+
+        let memory = Self {
+            game,
+            il2cpp,
+            version: profile.version,
+            build_id: version::build_id(game),
+            is_loading: profile.is_loading.pointer(),
+            level_id,
+            is_loading_2: profile.is_loading_2.pointer(),
+            tocman_qte: LazyPointer::new(profile.tocman_qte.pointer()),
+            stage_time: profile.stage_time.pointer(),
+            is_paused: profile.is_paused.pointer(),
+        };
+
+        let mut message = ArrayString::<96>::new();
+        let _ = write!(message, "Game version: {}", memory.version_label());
+        timer.print_message(&message);
+
+        Some(memory)
+    }
+
+    /// Layout that resolved and the running build, e.g. "Release layout, build 6530A1F2"
+    pub fn version_label(&self) -> ArrayString<48> {
+        let mut label = ArrayString::new();
+        let _ = match self.build_id {
+            Some(build_id) => write!(label, "{} layout, build {build_id:08X}", self.version.name()),
+            None => write!(label, "{} layout, unknown build", self.version.name()),
+        };
+        label
+    }
+}
+
+impl MemorySource for Memory<'_> {
+    fn is_loading(&self) -> Option<bool> {
+        self.is_loading.deref(self.game, &self.il2cpp.module, &self.il2cpp.image).ok()
+    }
+
+    fn is_loading_2(&self) -> Option<u64> {
+        self.is_loading_2.deref(self.game, &self.il2cpp.module, &self.il2cpp.image).ok()
+    }
+
+    fn level_id(&self) -> Option<u32> {
+        self.level_id.deref(self.game, &self.il2cpp.module, &self.il2cpp.image).ok()
+    }
+
+    fn tocman_qte(&self) -> Option<bool> {
+        let address = self.tocman_qte.resolve(self.game, &self.il2cpp.module, &self.il2cpp.image)?;
+        let value = self.game.read(address).ok();
+        if value.is_none() {
+            self.tocman_qte.release();
+        }
+        value
+    }
+
+    fn stage_time(&self) -> Option<f32> {
+        self.stage_time.deref(self.game, &self.il2cpp.module, &self.il2cpp.image).ok()
+    }
+
+    fn is_paused(&self) -> Option<bool> {
+        self.is_paused.deref(self.game, &self.il2cpp.module, &self.il2cpp.image).ok()
+    }
+
+    fn leave_scene(&self) {
+        self.tocman_qte.release();
+    }
+}
+
+/// Pointer to a field of a singleton that only lives as long as its scene, such as a boss.
+/// The field's address is resolved on first use and kept until `release()`.
+struct LazyPointer {
+    pointer: UnityPointer<2>,
+    address: Cell<Option<Address>>,
+}
+
+impl LazyPointer {
+    fn new(pointer: UnityPointer<2>) -> Self {
+        Self {
+            pointer,
+            address: Cell::new(None),
+        }
+    }
+
+    fn resolve(&self, game: &Process, module: &Module, image: &Image) -> Option<Address> {
+        if let Some(address) = self.address.get() {
+            return Some(address);
+        }
+
+        let address = self.pointer.deref_offsets(game, module, image).ok()?;
+        self.address.set(Some(address));
+        Some(address)
+    }
+
+    fn release(&self) {
+        self.address.set(None);
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum PointerStatus {
+    /// Not read yet, because it's only looked up in a scene that hasn't been entered
+    #[default]
+    NotNeeded,
+    /// No read has succeeded yet
+    NeverResolved,
+    /// The last read succeeded
+    Resolved,
+    /// Reads used to succeed, but the last one failed
+    Failing,
+}
+
+impl PointerStatus {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::NotNeeded => "not needed yet",
+            Self::NeverResolved => "never resolved",
+            Self::Resolved => "resolved",
+            Self::Failing => "failing",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PointerHealth {
+    status: PointerStatus,
+    reported: Option<PointerStatus>,
+}
+
+impl PointerHealth {
+    /// Updates the status from the outcome of a read, passing the value through
+    pub fn record<T>(&mut self, value: Option<T>) -> Option<T> {
+        self.status = match (&value, self.status) {
+            (Some(_), _) => PointerStatus::Resolved,
+            (None, PointerStatus::NotNeeded | PointerStatus::NeverResolved) => PointerStatus::NeverResolved,
+            (None, _) => PointerStatus::Failing,
+        };
+        value
+    }
+
+    pub fn status(&self) -> PointerStatus {
+        self.status
+    }
+
+    /// Returns the current status if it hasn't been reported yet
+    pub fn take_change(&mut self) -> Option<PointerStatus> {
+        if self.reported == Some(self.status) {
+            return None;
+        }
+        self.reported = Some(self.status);
+        Some(self.status)
+    }
+}
+
+/// Health of every pointer exposed by `MemorySource`
+#[derive(Default)]
+pub struct MemoryHealth {
+    pub is_loading: PointerHealth,
+    pub is_loading_2: PointerHealth,
+    pub level_id: PointerHealth,
+    pub tocman_qte: PointerHealth,
+    pub stage_time: PointerHealth,
+    pub is_paused: PointerHealth,
+}
+
+impl MemoryHealth {
+    pub fn pointers_mut(&mut self) -> [(&'static str, &mut PointerHealth); 6] {
+        [
+            ("is_loading", &mut self.is_loading),
+            ("is_loading_2", &mut self.is_loading_2),
+            ("level_id", &mut self.level_id),
+            ("tocman_qte", &mut self.tocman_qte),
+            ("stage_time", &mut self.stage_time),
+            ("is_paused", &mut self.is_paused),
+        ]
+    }
+}
+
+/// Values returned by `ScriptedMemory` for a single tick
+#[cfg(test)]
+#[derive(Copy, Clone, Default)]
+pub struct Frame {
+    pub is_loading: Option<bool>,
+    pub is_loading_2: Option<u64>,
+    pub level_id: Option<u32>,
+    pub tocman_qte: Option<bool>,
+    pub stage_time: Option<f32>,
+    pub is_paused: Option<bool>,
+}
+
+/// In-memory stand-in for the game, so the watcher pipeline can run without a live process.
+/// Set `frame` before each call to `update_loop()`.
+#[cfg(test)]
+#[derive(Default)]
+pub struct ScriptedMemory {
+    pub frame: Frame,
+}
+
+#[cfg(test)]
+impl MemorySource for ScriptedMemory {
+    fn is_loading(&self) -> Option<bool> {
+        self.frame.is_loading
+    }
+
+    fn is_loading_2(&self) -> Option<u64> {
+        self.frame.is_loading_2
+    }
+
+    fn level_id(&self) -> Option<u32> {
+        self.frame.level_id
+    }
+
+    fn tocman_qte(&self) -> Option<bool> {
+        self.frame.tocman_qte
+    }
+
+    fn stage_time(&self) -> Option<f32> {
+        self.frame.stage_time
+    }
+
+    fn is_paused(&self) -> Option<bool> {
+        self.frame.is_paused
+    }
+}
@@ -0,0 +1,428 @@
+use core::fmt::Write;
+
+use asr::{
+    settings::{gui::Title, Gui, Map, Value},
+    string::ArrayString,
+};
+
+use crate::{
+    level::{Level, World},
+    splits::SPLITS,
+};
+
+/// Settings map key remembering which preset was last written into the level splits
+const APPLIED_PRESET_KEY: &str = "applied_preset";
+
+#[derive(Gui)]
+pub struct Settings {
+    /// Category preset
+    ///
+    /// Picking a preset sets the level splits and the start trigger. Anything changed by hand afterwards is kept until another preset is picked.
+    pub preset: Preset,
+    /// World for the Single World preset
+    pub preset_world: World,
+    #[default = true]
+    /// => Enable auto start
+    pub start: bool,
+    /// Start trigger
+    pub start_trigger: StartTrigger,
+    #[default = false]
+    /// => Enable auto reset
+    pub reset: bool,
+    /// Reset trigger
+    pub reset_trigger: ResetTrigger,
+    /// Timing method
+    ///
+    /// The in-game clock falls back to load removal for as long as the game's level clock can't be read.
+    pub timing_method: TimingMethod,
+    /// Split when
+    pub split_on: SplitOn,
+    #[default = false]
+    /// Split once per world
+    ///
+    /// Instead of one split per level, splits when leaving the last enabled level of each world, or its boss stage if none of its levels are enabled. For splits files with one segment per world.
+    pub world_splits: bool,
+    #[default = false]
+    /// Skip splits for disabled levels
+    ///
+    /// Completing a disabled level skips its split instead of ignoring it, for splits files that still have a segment for every level.
+    pub skip_disabled: bool,
+    #[default = false]
+    /// Follow the route
+    ///
+    /// Only splits when the completed level is the one the current segment expects, going by the enabled levels in order. Replaying an earlier level or going off route is logged instead.
+    pub route_splits: bool,
+    #[default = false]
+    /// Individual level mode
+    ///
+    /// Entering any enabled level starts the timer, reaching its results screen splits, and restarting or quitting the level resets.
+    pub il_mode: bool,
+    #[default = true]
+    /// Remove scene transitions
+    ///
+    /// Pauses game time while SceneManager is processing a scene change
+    pub load_scene_processing: bool,
+    #[default = true]
+    /// Remove loading screens
+    ///
+    /// Pauses game time while GameStateManager shows a loading screen
+    pub load_screen: bool,
+    #[default = false]
+    /// Remove the results screen
+    ///
+    /// Pauses game time while the end-of-level tally is shown
+    pub load_results: bool,
+    #[default = false]
+    /// Remove the pause menu
+    ///
+    /// Pauses game time while the game is paused. Meant for individual levels and practice, leave it off for full-game runs.
+    pub load_pause: bool,
+    #[heading_level = 0]
+    /// Level splits
+    _level_splits: Title,
+    #[heading_level = 1]
+    /// Pirate
+    _pirate: Title,
+    #[default = true]
+    /// Enable Pirate splits
+    ///
+    /// Turning this off disables every level below without changing their own checkboxes
+    pub pirate_world: bool,
+    #[default = true]
+    /// 1.1 - Buccaneer Beach
+    pub buccaneer_beach: bool,
+    #[default = true]
+    /// 1.2 - Corsair's Cove
+    pub corsair_cove: bool,
+    #[default = true]
+    /// 1.3 - Crazy Cannonade
+    pub crazy_cannonade: bool,
+    #[default = true]
+    /// 1.4 - HMS Windbag
+    pub hms_windbag: bool,
+    #[heading_level = 1]
+    /// Ruins
+    _ruins: Title,
+    #[default = true]
+    /// Enable Ruins splits
+    ///
+    /// Turning this off disables every level below without changing their own checkboxes
+    pub ruins_world: bool,
+    #[default = true]
+    /// 2.1 - Crisis Cavern
+    pub crisis_cavern: bool,
+    #[default = true]
+    /// 2.2 - Manic Mines
+    pub manic_mines: bool,
+    #[default = true]
+    /// 2.3 - Anubis Rex
+    pub anubis_rex: bool,
+    #[heading_level = 1]
+    /// Space
+    _space: Title,
+    #[default = true]
+    /// Enable Space splits
+    ///
+    /// Turning this off disables every level below without changing their own checkboxes
+    pub space_world: bool,
+    #[default = true]
+    /// 3.1 - Space Race
+    pub space_race: bool,
+    #[default = true]
+    /// 3.2 - Far Out
+    pub far_out: bool,
+    #[default = true]
+    /// 3.3 - Gimme Space
+    pub gimme_space: bool,
+    #[default = true]
+    /// 3.4 - King Galaxian
+    pub king_galaxian: bool,
+    #[heading_level = 1]
+    /// Funhouse
+    _funhouse: Title,
+    #[default = true]
+    /// Enable Funhouse splits
+    ///
+    /// Turning this off disables every level below without changing their own checkboxes
+    pub funhouse_world: bool,
+    #[default = true]
+    /// 4.1 - Clowning Around
+    pub clowning_around: bool,
+    #[default = true]
+    /// 4.2 - Barrel Blast
+    pub barrel_blast: bool,
+    #[default = true]
+    /// 4.3 - Barrel Dizzy
+    pub barrel_dizzy: bool,
+    #[default = true]
+    /// 4.4 - Clown Prix
+    pub clown_prix: bool,
+    #[heading_level = 1]
+    /// Mill
+    _mill: Title,
+    #[default = true]
+    /// Enable Mill splits
+    ///
+    /// Turning this off disables every level below without changing their own checkboxes
+    pub mill_world: bool,
+    #[default = true]
+    /// 5.1 - Perilous Pipes
+    pub perilous_pipes: bool,
+    #[default = true]
+    /// 5.2 - Under Pressure
+    pub under_pressure: bool,
+    #[default = true]
+    /// 5.3 - Down the Tubes
+    pub down_the_tubes: bool,
+    #[default = true]
+    /// 5.4 - Krome Keeper
+    pub krome_keeper: bool,
+    #[heading_level = 1]
+    /// Haunted
+    _haunted: Title,
+    #[default = true]
+    /// Enable Haunted splits
+    ///
+    /// Turning this off disables every level below without changing their own checkboxes
+    pub haunted_world: bool,
+    #[default = true]
+    /// 6.1 - Ghostly Garden
+    pub ghostly_garden: bool,
+    #[default = true]
+    /// 6.2 - Creepy Catacombs
+    pub creepy_catacombs: bool,
+    #[default = true]
+    /// 6.3 - Grave Danger
+    pub grave_danger: bool,
+    #[default = true]
+    /// 6.4 - Toc-Man's Lair
+    pub toc_man_lair: bool,
+}
+
+impl Settings {
+    /// Per-world master toggle
+    pub fn world_enabled(&self, world: World) -> bool {
+        self.world_toggle(world).1
+    }
+
+    /// Settings key and value of the world's master toggle
+    fn world_toggle(&self, world: World) -> (&'static str, bool) {
+        macro_rules! toggle {
+            ($field:ident) => {
+                (stringify!($field), self.$field)
+            };
+        }
+
+        match world {
+            World::Pirate => toggle!(pirate_world),
+            World::Ruins => toggle!(ruins_world),
+            World::Space => toggle!(space_world),
+            World::Funhouse => toggle!(funhouse_world),
+            World::Mill => toggle!(mill_world),
+            World::Haunted => toggle!(haunted_world),
+        }
+    }
+
+    /// Writes the selected preset into the stored settings, once each time a different preset is picked.
+    /// `applied` remembers the last preset handled, so the settings map is only loaded when the selection changes.
+    pub fn apply_preset(&self, applied: &mut Option<ArrayString<32>>) {
+        let mut preset_key = ArrayString::<32>::new();
+        let _ = match self.preset {
+            Preset::SingleWorld => write!(preset_key, "{}:{}", self.preset.key(), self.preset_world.name()),
+            _ => write!(preset_key, "{}", self.preset.key()),
+        };
+
+        if *applied == Some(preset_key) {
+            return;
+        }
+
+        // Starts over if the runner changes a setting while the preset is being written
+        loop {
+            let old = Map::load();
+            let stored = old
+                .get(APPLIED_PRESET_KEY)
+                .and_then(|value| value.get_array_string::<32>()?.ok());
+            if stored == Some(preset_key) {
+                break;
+            }
+
+            let map = old.clone();
+            if self.preset != Preset::Custom {
+                for world in World::ALL {
+                    map.insert(self.world_toggle(world).0, &Value::from(self.preset.includes_world(world, self.preset_world)));
+                }
+                for entry in &SPLITS {
+                    map.insert(entry.key, &Value::from(self.preset.includes(entry.level, self.preset_world)));
+                }
+                map.insert("start_trigger", &Value::from(self.preset.start_trigger().key()));
+            }
+            map.insert(APPLIED_PRESET_KEY, &Value::from(preset_key.as_str()));
+
+            if map.store_if_unchanged(&old) {
+                break;
+            }
+        }
+
+        *applied = Some(preset_key);
+    }
+}
+
+/// Same values as the `#[default]` attributes, since host tests have no settings map to register against
+#[cfg(test)]
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            preset: Preset::Custom,
+            preset_world: World::Pirate,
+            start: true,
+            start_trigger: StartTrigger::NewGame,
+            reset: false,
+            reset_trigger: ResetTrigger::NewGame,
+            timing_method: TimingMethod::LoadRemoved,
+            split_on: SplitOn::Exit,
+            world_splits: false,
+            skip_disabled: false,
+            route_splits: false,
+            il_mode: false,
+            load_scene_processing: true,
+            load_screen: true,
+            load_results: false,
+            load_pause: false,
+            _level_splits: Default::default(),
+            _pirate: Default::default(),
+            pirate_world: true,
+            buccaneer_beach: true,
+            corsair_cove: true,
+            crazy_cannonade: true,
+            hms_windbag: true,
+            _ruins: Default::default(),
+            ruins_world: true,
+            crisis_cavern: true,
+            manic_mines: true,
+            anubis_rex: true,
+            _space: Default::default(),
+            space_world: true,
+            space_race: true,
+            far_out: true,
+            gimme_space: true,
+            king_galaxian: true,
+            _funhouse: Default::default(),
+            funhouse_world: true,
+            clowning_around: true,
+            barrel_blast: true,
+            barrel_dizzy: true,
+            clown_prix: true,
+            _mill: Default::default(),
+            mill_world: true,
+            perilous_pipes: true,
+            under_pressure: true,
+            down_the_tubes: true,
+            krome_keeper: true,
+            _haunted: Default::default(),
+            haunted_world: true,
+            ghostly_garden: true,
+            creepy_catacombs: true,
+            grave_danger: true,
+            toc_man_lair: true,
+        }
+    }
+}
+
+#[derive(Gui, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Custom
+    #[default]
+    Custom,
+    /// Any%
+    AnyPercent,
+    /// 100%
+    HundredPercent,
+    /// All Bosses
+    AllBosses,
+    /// Single World
+    SingleWorld,
+}
+
+impl Preset {
+    /// Variant name, as stored in the settings map
+    const fn key(self) -> &'static str {
+        match self {
+            Self::Custom => "Custom",
+            Self::AnyPercent => "AnyPercent",
+            Self::HundredPercent => "HundredPercent",
+            Self::AllBosses => "AllBosses",
+            Self::SingleWorld => "SingleWorld",
+        }
+    }
+
+    /// Full-game categories split on every level
+    fn includes(self, level: Level, world: World) -> bool {
+        match self {
+            Self::Custom | Self::AnyPercent | Self::HundredPercent => true,
+            Self::AllBosses => level.is_boss(),
+            Self::SingleWorld => level.world() == world,
+        }
+    }
+
+    /// Whether the world's master toggle is turned on
+    fn includes_world(self, world: World, preset_world: World) -> bool {
+        self != Self::SingleWorld || world == preset_world
+    }
+
+    const fn start_trigger(self) -> StartTrigger {
+        match self {
+            Self::SingleWorld => StartTrigger::LevelEntry,
+            _ => StartTrigger::NewGame,
+        }
+    }
+}
+
+#[derive(Gui, Clone, Copy, PartialEq, Eq)]
+pub enum StartTrigger {
+    /// Starting a new file
+    #[default]
+    NewGame,
+    /// Entering any enabled level
+    LevelEntry,
+}
+
+impl StartTrigger {
+    /// Variant name, as stored in the settings map
+    const fn key(self) -> &'static str {
+        match self {
+            Self::NewGame => "NewGame",
+            Self::LevelEntry => "LevelEntry",
+        }
+    }
+}
+
+#[derive(Gui, Clone, Copy, PartialEq, Eq)]
+pub enum ResetTrigger {
+    /// Returning to the title screen
+    TitleScreen,
+    /// Starting a new file
+    #[default]
+    NewGame,
+    /// Either of the above
+    Both,
+}
+
+#[derive(Gui, Clone, Copy, PartialEq, Eq)]
+pub enum SplitOn {
+    /// Leaving a level for the hub
+    #[default]
+    Exit,
+    /// Entering a level from the hub
+    Entry,
+    /// Both entering and leaving a level
+    Both,
+}
+
+#[derive(Gui, Clone, Copy, PartialEq, Eq)]
+pub enum TimingMethod {
+    /// Real time without loads
+    #[default]
+    LoadRemoved,
+    /// In-game level clock
+    InGame,
+}
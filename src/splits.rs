@@ -0,0 +1,141 @@
+use crate::{
+    level::{Level, World},
+    settings::{Settings, SplitOn},
+};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SplitBehavior {
+    /// Split when going back to the hub from the results screen or a boss cutscene
+    Exit,
+    /// Same as `Exit`, but also split the moment the final QTE succeeds
+    BossQte,
+}
+
+pub struct SplitEntry {
+    pub level: Level,
+    /// Key of the level's entry in the settings map
+    pub key: &'static str,
+    pub setting: fn(&Settings) -> bool,
+    pub behavior: SplitBehavior,
+}
+
+impl SplitEntry {
+    pub fn find(level: Level) -> Option<&'static Self> {
+        SPLITS.iter().find(|entry| entry.level == level)
+    }
+
+    /// The level's own setting, overridden by its world's master toggle
+    pub fn enabled(&self, settings: &Settings) -> bool {
+        settings.world_enabled(self.level.world()) && (self.setting)(settings)
+    }
+}
+
+/// Level closing `world` for world splits: its last enabled level, or its boss stage if none are enabled
+pub fn world_closing_level(settings: &Settings, world: World) -> Option<Level> {
+    SPLITS
+        .iter()
+        .rev()
+        .find(|entry| entry.level.world() == world && entry.enabled(settings))
+        .map(|entry| entry.level)
+        .or_else(|| Level::ALL.into_iter().find(|val| val.world() == world && val.is_boss()))
+}
+
+/// Level expected to end the segment at `split_index`. Presets only write the level settings,
+/// so the route is always the enabled levels in game order, plus disabled ones when their splits are skipped.
+pub fn route_level(settings: &Settings, split_index: u64) -> Option<Level> {
+    if settings.world_splits {
+        return World::ALL
+            .into_iter()
+            .filter(|&world| settings.world_enabled(world) || settings.skip_disabled)
+            .filter_map(|world| world_closing_level(settings, world))
+            .nth(split_index as usize);
+    }
+
+    let mut route = SPLITS.iter().filter(|entry| entry.enabled(settings) || settings.skip_disabled);
+    let split_index = split_index as usize;
+
+    let entry = match settings.split_on {
+        SplitOn::Exit => route.nth(split_index),
+        // Splitting on both entry and exit gives every level two segments
+        SplitOn::Both => route.nth(split_index / 2),
+        // Entering the last level leaves one more segment, ended by its final QTE
+        SplitOn::Entry => {
+            let len = route.clone().count();
+            if split_index < len {
+                route.nth(split_index)
+            } else {
+                route.next_back().filter(|entry| split_index == len && entry.behavior == SplitBehavior::BossQte)
+            }
+        }
+    };
+
+    entry.map(|entry| entry.level)
+}
+
+/// Entry for `level`, stored under the settings field of the same name, so the key can't drift from the field
+macro_rules! entry {
+    ($level:ident, $field:ident) => {
+        entry!($level, $field, Exit)
+    };
+    ($level:ident, $field:ident, $behavior:ident) => {
+        SplitEntry {
+            level: Level::$level,
+            key: stringify!($field),
+            setting: |s| s.$field,
+            behavior: SplitBehavior::$behavior,
+        }
+    };
+}
+
+/// One entry per level, in game order. The level name shown to the runner comes from `Level::name()`.
+pub const SPLITS: [SplitEntry; Level::ALL.len()] = [
+    entry!(BuccaneerBeach, buccaneer_beach),
+    entry!(CorsairsCove, corsair_cove),
+    entry!(CrazyCannonade, crazy_cannonade),
+    entry!(HmsWindbag, hms_windbag),
+    entry!(CrisisCavern, crisis_cavern),
+    entry!(ManicMines, manic_mines),
+    entry!(AnubisRex, anubis_rex),
+    entry!(SpaceRace, space_race),
+    entry!(FarOut, far_out),
+    entry!(GimmeSpace, gimme_space),
+    entry!(KingGalaxian, king_galaxian),
+    entry!(ClowningAround, clowning_around),
+    entry!(BarrelBlast, barrel_blast),
+    entry!(BarrelDizzy, barrel_dizzy),
+    entry!(ClownPrix, clown_prix),
+    entry!(PerilousPipes, perilous_pipes),
+    entry!(UnderPressure, under_pressure),
+    entry!(DownTheTubes, down_the_tubes),
+    entry!(KromeKeeper, krome_keeper),
+    entry!(GhostlyGarden, ghostly_garden),
+    entry!(CreepyCatacombs, creepy_catacombs),
+    entry!(GraveDanger, grave_danger),
+    entry!(TocMansLair, toc_man_lair, BossQte),
+];
+
+// Every level must have exactly one entry, in the same order as `Level::ALL`
+const _: () = {
+    let mut i = 0;
+    while i < SPLITS.len() {
+        assert!(SPLITS[i].level as u32 == Level::ALL[i] as u32, "SPLITS is out of sync with Level::ALL");
+        i += 1;
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_split_has_its_own_setting() {
+        for (i, entry) in SPLITS.iter().enumerate() {
+            assert!(
+                SPLITS[..i].iter().all(|other| other.key != entry.key),
+                "{:?} shares the setting `{}`",
+                entry.level,
+                entry.key
+            );
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use asr::{time::Duration, timer::TimerState};
+
+/// Everything the splitter does to the LiveSplit timer, including variables and log messages, goes through here
+pub trait TimerSink {
+    fn state(&self) -> TimerState;
+    /// Index of the segment being timed, `None` while the timer isn't running
+    fn current_split_index(&self) -> Option<u64>;
+    fn start(&mut self);
+    fn split(&mut self);
+    fn skip_split(&mut self);
+    fn reset(&mut self);
+    fn pause_game_time(&mut self);
+    fn resume_game_time(&mut self);
+    fn set_game_time(&mut self, time: Duration);
+    fn set_variable(&mut self, key: &str, value: &str);
+    fn print_message(&mut self, message: &str);
+}
+
+/// The actual LiveSplit timer, driven through the asr runtime
+pub struct AsrTimer;
+
+impl TimerSink for AsrTimer {
+    fn state(&self) -> TimerState {
+        asr::timer::state()
+    }
+
+    fn current_split_index(&self) -> Option<u64> {
+        asr::timer::current_split_index()
+    }
+
+    fn start(&mut self) {
+        asr::timer::start()
+    }
+
+    fn split(&mut self) {
+        asr::timer::split()
+    }
+
+    fn skip_split(&mut self) {
+        asr::timer::skip_split()
+    }
+
+    fn reset(&mut self) {
+        asr::timer::reset()
+    }
+
+    fn pause_game_time(&mut self) {
+        asr::timer::pause_game_time()
+    }
+
+    fn resume_game_time(&mut self) {
+        asr::timer::resume_game_time()
+    }
+
+    fn set_game_time(&mut self, time: Duration) {
+        asr::timer::set_game_time(time)
+    }
+
+    fn set_variable(&mut self, key: &str, value: &str) {
+        asr::timer::set_variable(key, value)
+    }
+
+    fn print_message(&mut self, message: &str) {
+        asr::print_message(message)
+    }
+}
+
+#[cfg(test)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimerEvent {
+    Start,
+    Split,
+    SkipSplit,
+    Reset,
+    PauseGameTime,
+    ResumeGameTime,
+    SetGameTime(Duration),
+}
+
+/// Mock timer for host tests: keeps track of the timer state and records every call in order
+#[cfg(test)]
+pub struct RecordingTimer {
+    pub state: TimerState,
+    pub split_index: Option<u64>,
+    pub events: std::vec::Vec<TimerEvent>,
+    pub variables: std::collections::BTreeMap<std::string::String, std::string::String>,
+    pub messages: std::vec::Vec<std::string::String>,
+}
+
+#[cfg(test)]
+impl Default for RecordingTimer {
+    fn default() -> Self {
+        Self {
+            state: TimerState::NotRunning,
+            split_index: None,
+            events: std::vec::Vec::new(),
+            variables: std::collections::BTreeMap::new(),
+            messages: std::vec::Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl TimerSink for RecordingTimer {
+    fn state(&self) -> TimerState {
+        self.state
+    }
+
+    fn current_split_index(&self) -> Option<u64> {
+        self.split_index
+    }
+
+    fn start(&mut self) {
+        self.state = TimerState::Running;
+        self.split_index = Some(0);
+        self.events.push(TimerEvent::Start);
+    }
+
+    fn split(&mut self) {
+        self.split_index = self.split_index.map(|val| val + 1);
+        self.events.push(TimerEvent::Split);
+    }
+
+    fn skip_split(&mut self) {
+        self.split_index = self.split_index.map(|val| val + 1);
+        self.events.push(TimerEvent::SkipSplit);
+    }
+
+    fn reset(&mut self) {
+        self.state = TimerState::NotRunning;
+        self.split_index = None;
+        self.events.push(TimerEvent::Reset);
+    }
+
+    fn pause_game_time(&mut self) {
+        self.events.push(TimerEvent::PauseGameTime);
+    }
+
+    fn resume_game_time(&mut self) {
+        self.events.push(TimerEvent::ResumeGameTime);
+    }
+
+    fn set_game_time(&mut self, time: Duration) {
+        self.events.push(TimerEvent::SetGameTime(time));
+    }
+
+    fn set_variable(&mut self, key: &str, value: &str) {
+        self.variables.insert(key.into(), value.into());
+    }
+
+    fn print_message(&mut self, message: &str) {
+        self.messages.push(message.into());
+    }
+}
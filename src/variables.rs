@@ -0,0 +1,87 @@
+use core::fmt::Write;
+
+use asr::string::ArrayString;
+
+use crate::{level::Level, logic::Watchers, timer::TimerSink};
+
+/// Last value sent for a timer variable, so that it's only sent again once it changes
+pub struct Variable<T>(Option<T>);
+
+impl<T> Default for Variable<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T: PartialEq> Variable<T> {
+    /// Stores the value, returning whether it differs from the one sent before
+    pub fn update(&mut self, value: T) -> bool {
+        if self.0.as_ref() == Some(&value) {
+            return false;
+        }
+        self.0 = Some(value);
+        true
+    }
+}
+
+#[derive(Default)]
+pub struct Variables {
+    level: Variable<Option<Level>>,
+    scene_id: Variable<Option<u32>>,
+    loading: Variable<Option<bool>>,
+    levels_completed: Variable<u32>,
+    scene_processing: Variable<Option<bool>>,
+    load_screen: Variable<Option<bool>>,
+}
+
+/// Sends every timer variable whose value changed since the last tick
+pub fn publish(timer: &mut impl TimerSink, watchers: &mut Watchers) {
+    let variables = &mut watchers.variables;
+
+    let scene_id = watchers.level_id_unfiltered.pair.and_then(|val| val.current);
+    if variables.scene_id.update(scene_id) {
+        match scene_id {
+            Some(scene_id) => timer.set_variable("Scene id", &number(scene_id)),
+            None => timer.set_variable("Scene id", "unknown"),
+        }
+    }
+
+    let level = scene_id.and_then(|val| Level::try_from(val).ok());
+    if variables.level.update(level) {
+        timer.set_variable("Level", level.map_or("-", Level::name));
+        timer.set_variable("World", level.map_or("-", |val| val.world().name()));
+    }
+
+    let loading = watchers.is_loading.pair.and_then(|val| val.current);
+    if variables.loading.update(loading) {
+        timer.set_variable("Loading", flag(loading));
+    }
+
+    if variables.levels_completed.update(watchers.levels_completed) {
+        timer.set_variable("Levels completed", &number(watchers.levels_completed));
+    }
+
+    let scene_processing = watchers.scene_processing.pair.and_then(|val| val.current);
+    if variables.scene_processing.update(scene_processing) {
+        timer.set_variable("Load: scene transition", flag(scene_processing));
+    }
+
+    let load_screen = watchers.load_screen.pair.and_then(|val| val.current);
+    if variables.load_screen.update(load_screen) {
+        timer.set_variable("Load: loading screen", flag(load_screen));
+    }
+}
+
+fn number(value: u32) -> ArrayString<10> {
+    let mut buf = ArrayString::new();
+    let _ = write!(buf, "{value}");
+    buf
+}
+
+fn flag(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "unknown",
+    }
+}
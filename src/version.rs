@@ -0,0 +1,62 @@
+use asr::{game_engine::unity::il2cpp::UnityPointer, Process};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameVersion {
+    /// Layout of the launch build
+    Release,
+}
+
+impl GameVersion {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Release => "Release",
+        }
+    }
+}
+
+/// A static class and the chain of fields leading to the value of interest
+pub struct PointerPath {
+    pub class: &'static str,
+    pub fields: &'static [&'static str],
+}
+
+impl PointerPath {
+    pub fn pointer(&self) -> UnityPointer<2> {
+        UnityPointer::new(self.class, 1, self.fields)
+    }
+}
+
+/// Pointer paths used by `Memory` for a given game build
+pub struct PointerProfile {
+    pub version: GameVersion,
+    pub is_loading: PointerPath,
+    pub level_id: PointerPath,
+    pub is_loading_2: PointerPath,
+    pub tocman_qte: PointerPath,
+    pub stage_time: PointerPath,
+    pub is_paused: PointerPath,
+}
+
+/// Known layouts, newest first. `Memory::init` picks the first one that resolves on the running game,
+/// so when a patch renames a field, add a new profile on top.
+pub const PROFILES: &[PointerProfile] = &[PointerProfile {
+    version: GameVersion::Release,
+    is_loading: PointerPath { class: "SceneManager", fields: &["s_sInstance", "m_bProcessing"] },
+    level_id: PointerPath { class: "SceneManager", fields: &["s_sInstance", "m_eCurrentScene"] },
+    is_loading_2: PointerPath { class: "GameStateManager", fields: &["s_sInstance", "loadScr"] },
+    tocman_qte: PointerPath { class: "BossTocman", fields: &["s_sInstance", "m_qteSuccess"] },
+    // Not yet checked against a dump of the game's il2cpp metadata. If it doesn't exist, in-game timing falls back to
+    // load removal and says so through the "Level clock" variable.
+    stage_time: PointerPath { class: "GameStateManager", fields: &["s_sInstance", "m_fStageTime"] },
+    // Not yet checked against a dump of the game's il2cpp metadata. If it doesn't exist, the "Pause menu" variable
+    // says so while removing the pause menu is turned on.
+    is_paused: PointerPath { class: "GameStateManager", fields: &["s_sInstance", "m_bPause"] },
+}];
+
+/// Link timestamp from the PE header of GameAssembly.dll, which changes with every game build.
+/// Only reported for now, so bug reports say which build they came from.
+pub fn build_id(game: &Process) -> Option<u32> {
+    let base = game.get_module_address("GameAssembly.dll").ok()?;
+    let pe_header = base.add(game.read::<u32>(base.add(0x3C)).ok()? as u64);
+    game.read::<u32>(pe_header.add(0x8)).ok()
+}